@@ -1,4 +1,7 @@
+use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Mutex;
 
 struct Board {
     color_count: u32,
@@ -28,6 +31,9 @@ impl Display for MatchKeys {
 
 impl Board {
     fn new(color_count: u32, hole_count: u32) -> Board {
+        // pack_match packs exact_count/color_count into a u8, which only
+        // holds boards with hole_count <= 15.
+        debug_assert!(hole_count <= 15);
         Board {
             color_count,
             hole_count,
@@ -82,20 +88,173 @@ impl Board {
             .map(|c| color_chars.iter().position(|&x| x == c).unwrap() as u32)
             .fold(0, |acc, x| acc * self.color_count + x)
     }
+    // exact_count and color_count both only ever range over 0..=hole_count,
+    // so the pair fits in one byte as long as hole_count <= 15 (see Board::new).
+    fn pack_match(&self, match_keys: MatchKeys) -> u8 {
+        (match_keys.exact_count * (self.hole_count + 1) + match_keys.color_count) as u8
+    }
+    fn unpack_match(&self, packed: u8) -> MatchKeys {
+        let divisor = self.hole_count + 1;
+        let packed = packed as u32;
+        MatchKeys::new(packed / divisor, packed % divisor)
+    }
+}
+
+enum MatchCache {
+    // Full N×N table, built once up front.
+    Full(Vec<Vec<u8>>),
+    // Rows computed on demand and memoized only for guesses actually looked up.
+    Lazy(Mutex<HashMap<u32, Vec<u8>>>),
+}
+
+impl MatchCache {
+    fn with_row<R>(&self, board: &Board, guess: u32, f: impl FnOnce(&[u8]) -> R) -> R {
+        match self {
+            MatchCache::Full(matches) => f(&matches[guess as usize]),
+            MatchCache::Lazy(cache) => {
+                let mut cache = cache.lock().unwrap();
+                let row = cache.entry(guess).or_insert_with(|| {
+                    (0..board.total_pattern_count())
+                        .map(|pattern| board.pack_match(board.compute_match(guess, pattern)))
+                        .collect()
+                });
+                f(row)
+            }
+        }
+    }
+
+    // Like with_row, but only computes the entries for `patterns` and never
+    // memoizes them. get_guess uses this to score every possible guess
+    // without caching a row per guess (which would leave the cache holding
+    // the full N×N table after the very first move) and without paying for
+    // patterns that have already been ruled out.
+    fn scan_row(&self, board: &Board, guess: u32, patterns: &[u32]) -> Vec<u8> {
+        match self {
+            MatchCache::Full(matches) => {
+                let row = &matches[guess as usize];
+                patterns
+                    .iter()
+                    .map(|&pattern| row[pattern as usize])
+                    .collect()
+            }
+            MatchCache::Lazy(cache) => {
+                if let Some(row) = cache.lock().unwrap().get(&guess) {
+                    return patterns
+                        .iter()
+                        .map(|&pattern| row[pattern as usize])
+                        .collect();
+                }
+                patterns
+                    .iter()
+                    .map(|&pattern| board.pack_match(board.compute_match(guess, pattern)))
+                    .collect()
+            }
+        }
+    }
 }
 
 struct Game {
     board: Board,
-    matches: Vec<Vec<MatchKeys>>,
+    matches: MatchCache,
     pattern_list: Vec<u32>,
 }
 
-fn compute_all_matches(board: &Board) -> Vec<Vec<MatchKeys>> {
+// Scores a candidate guess from the sizes of the groups its partition of
+// pattern_list produces. Higher is always better; the second component of
+// the returned pair breaks ties in the first.
+trait GroupScorer {
+    fn score(&self, group_sizes: &[u32], total: u32, is_candidate: bool) -> (f64, f64);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Minimax,
+    ExpectedSize,
+    MostParts,
+    Entropy,
+}
+
+impl Strategy {
+    const ALL: [Strategy; 4] = [
+        Strategy::Minimax,
+        Strategy::ExpectedSize,
+        Strategy::MostParts,
+        Strategy::Entropy,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::Minimax => "minimax",
+            Strategy::ExpectedSize => "expected-size",
+            Strategy::MostParts => "most-parts",
+            Strategy::Entropy => "entropy",
+        }
+    }
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Strategy, String> {
+        Strategy::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == s)
+            .ok_or_else(|| format!("Unknown strategy '{}'", s))
+    }
+}
+
+impl GroupScorer for Strategy {
+    fn score(&self, group_sizes: &[u32], total: u32, is_candidate: bool) -> (f64, f64) {
+        match self {
+            Strategy::Minimax => {
+                let row_max = group_sizes.iter().copied().max().unwrap_or(0);
+                let total_length: u32 = group_sizes.iter().map(|n| n.pow(2)).sum();
+                // Smaller is better for both, so negate to keep "higher is better"
+                (-(row_max as f64), -(total_length as f64))
+            }
+            Strategy::ExpectedSize => {
+                let sum_squares: u32 = group_sizes.iter().map(|n| n.pow(2)).sum();
+                (-(sum_squares as f64) / total as f64, 0.0)
+            }
+            Strategy::MostParts => (group_sizes.len() as f64, 0.0),
+            Strategy::Entropy => {
+                let entropy = group_sizes
+                    .iter()
+                    .map(|&n| {
+                        let p = n as f64 / total as f64;
+                        -p * p.log2()
+                    })
+                    .sum();
+                // On ties, prefer a guess that is itself still a valid code
+                (entropy, if is_candidate { 1.0 } else { 0.0 })
+            }
+        }
+    }
+}
+
+// On a tied score the smaller guess index wins, so the result is the same
+// regardless of the order the parallel reduction combines candidates in.
+fn pick_better_guess(a: ((f64, f64), u32), b: ((f64, f64), u32)) -> ((f64, f64), u32) {
+    match a.0.partial_cmp(&b.0).unwrap() {
+        std::cmp::Ordering::Greater => a,
+        std::cmp::Ordering::Less => b,
+        std::cmp::Ordering::Equal => {
+            if a.1 <= b.1 {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+fn compute_all_matches(board: &Board) -> Vec<Vec<u8>> {
     let pattern_count = board.total_pattern_count();
     (0..pattern_count)
+        .into_par_iter()
         .map(|pattern| {
             (0..pattern_count)
-                .map(|guess| board.compute_match(pattern, guess))
+                .map(|guess| board.pack_match(board.compute_match(pattern, guess)))
                 .collect()
         })
         .collect()
@@ -104,7 +263,7 @@ fn compute_all_matches(board: &Board) -> Vec<Vec<MatchKeys>> {
 impl Game {
     fn new(color_count: u32, hole_count: u32) -> Game {
         let board = Board::new(color_count, hole_count);
-        let matches = compute_all_matches(&board);
+        let matches = MatchCache::Full(compute_all_matches(&board));
         let pattern_list = (0..board.total_pattern_count()).collect();
         Game {
             board,
@@ -113,7 +272,19 @@ impl Game {
         }
     }
 
-    fn get_guess(&self) -> (u32, u32) {
+    // Computes match rows on demand instead of up front, trading CPU for memory.
+    fn new_lazy(color_count: u32, hole_count: u32) -> Game {
+        let board = Board::new(color_count, hole_count);
+        let matches = MatchCache::Lazy(Mutex::new(HashMap::new()));
+        let pattern_list = (0..board.total_pattern_count()).collect();
+        Game {
+            board,
+            matches,
+            pattern_list,
+        }
+    }
+
+    fn get_guess(&self, strategy: Strategy) -> (u32, u32) {
         // `pattern_list` contains all possible patterns at this point
 
         // If there is only one pattern left, we are done
@@ -122,56 +293,189 @@ impl Game {
             return (self.pattern_list[0], self.pattern_list.len() as u32);
         }
 
-        // The 3-tuple is
-        //   row_max: The current minimum of the maximum group length
-        //   total_length: The current minimum of the sum of all group lengths
-        //   guess: The pattern that minimizes the above two values
-        let mut best: Option<(u32, u32, u32)> = None;
-
-        // Consider *any* patterns as a potential guess
-        for guess in 0..self.matches.len() as u32 {
-            // Look up *all* `MatchKeys` for this guess
-            let match_row = &self.matches[guess as usize];
-            // Consider only the `MatchKeys` for the remaining patterns
-            let mut possibles: Vec<MatchKeys> = self
-                .pattern_list
-                .iter()
-                .map(|&pattern| match_row[pattern as usize])
-                .collect();
-            // Sort to group the same MatchKeys together
-            possibles.sort();
-
-            let mut row_max = 0;
-            let mut total_length: u32 = 0; // Sum of all group lengths
-            let mut rest = possibles.as_slice();
-            while let Some(&key) = rest.first() {
-                // The group length is the index of the first element that is not equal to `key`
-                let group_length = rest.iter().position(|&k| k != key).unwrap_or(rest.len());
-                row_max = row_max.max(group_length as u32);
-                total_length += (group_length as u32).pow(2);
-                rest = &rest[group_length..];
-            }
+        let total = self.pattern_list.len() as u32;
 
-            if let Some((best_row_max, best_total_length, _)) = best {
-                // Considering the `total_length` in case of a tie-breaker
-                // reduces the average number of guesses needed
-                if row_max < best_row_max
-                    || (row_max == best_row_max && total_length < best_total_length)
-                {
-                    best = Some((row_max, total_length, guess));
+        // Consider *any* patterns as a potential guess, in parallel, and keep
+        // the one with the best (score, guess) pair. `guess` breaks ties so
+        // the result is independent of how rayon splits and reduces the work.
+        let best = (0..self.board.total_pattern_count())
+            .into_par_iter()
+            .map(|guess| {
+                // Consider only the `MatchKeys` for the remaining patterns
+                let mut possibles: Vec<MatchKeys> = self
+                    .matches
+                    .scan_row(&self.board, guess, &self.pattern_list)
+                    .into_iter()
+                    .map(|packed| self.board.unpack_match(packed))
+                    .collect();
+                // Sort to group the same MatchKeys together
+                possibles.sort();
+
+                // Group lengths for the partition this guess induces on `pattern_list`
+                let mut group_sizes = Vec::new();
+                let mut rest = possibles.as_slice();
+                while let Some(&key) = rest.first() {
+                    // The group length is the index of the first element that is not equal to `key`
+                    let group_length = rest.iter().position(|&k| k != key).unwrap_or(rest.len());
+                    group_sizes.push(group_length as u32);
+                    rest = &rest[group_length..];
                 }
-            } else {
-                best = Some((row_max, total_length, guess));
-            }
-        }
 
-        (best.unwrap().2, self.pattern_list.len() as u32)
+                let is_candidate = self.pattern_list.contains(&guess);
+                let score = strategy.score(&group_sizes, total, is_candidate);
+                (score, guess)
+            })
+            .reduce_with(pick_better_guess)
+            .unwrap();
+
+        (best.1, self.pattern_list.len() as u32)
     }
 
     fn apply_match(&mut self, guess: u32, match_keys: MatchKeys) {
-        let guess_row = &self.matches[guess as usize];
-        self.pattern_list
-            .retain(|&p| guess_row[p as usize] == match_keys);
+        let packed = self.board.pack_match(match_keys);
+        let matches = &self.matches;
+        let board = &self.board;
+        let pattern_list = &mut self.pattern_list;
+        matches.with_row(board, guess, |guess_row| {
+            pattern_list.retain(|&p| guess_row[p as usize] == packed);
+        });
+    }
+}
+
+// Maps the sequence of MatchKeys responses seen so far (the root guess needs
+// none) to the (guess, possibles) pair Game::get_guess would compute there.
+type DecisionTree = HashMap<Vec<MatchKeys>, (u32, u32)>;
+
+fn build_decision_tree(
+    game: &mut Game,
+    strategy: Strategy,
+    path: &mut Vec<MatchKeys>,
+    memo: &mut HashMap<Vec<u32>, (u32, u32)>,
+    tree: &mut DecisionTree,
+) {
+    let mut canonical = game.pattern_list.clone();
+    canonical.sort();
+    let (guess, possibles) = *memo
+        .entry(canonical)
+        .or_insert_with(|| game.get_guess(strategy));
+    tree.insert(path.clone(), (guess, possibles));
+
+    if possibles <= 1 {
+        return;
+    }
+
+    let mut responses: Vec<MatchKeys> = game
+        .pattern_list
+        .iter()
+        .map(|&pattern| game.board.compute_match(guess, pattern))
+        .collect();
+    responses.sort();
+    responses.dedup();
+
+    for response in responses {
+        if response == MatchKeys::new(game.board.hole_count, 0) {
+            continue; // An exact match ends the game; no further guess is needed
+        }
+        let saved_pattern_list = game.pattern_list.clone();
+        game.apply_match(guess, response);
+        path.push(response);
+        build_decision_tree(game, strategy, path, memo, tree);
+        path.pop();
+        game.pattern_list = saved_pattern_list;
+    }
+}
+
+fn build_full_decision_tree(color_count: u32, hole_count: u32, strategy: Strategy) -> DecisionTree {
+    let mut game = Game::new(color_count, hole_count);
+    let mut tree = HashMap::new();
+    build_decision_tree(
+        &mut game,
+        strategy,
+        &mut Vec::new(),
+        &mut HashMap::new(),
+        &mut tree,
+    );
+    tree
+}
+
+fn decision_tree_max_depth(tree: &DecisionTree) -> u32 {
+    tree.iter()
+        .filter(|(_, &(_, possibles))| possibles == 1)
+        .map(|(path, _)| path.len() as u32 + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+// One line per node: the response path (`;`-separated MatchKeys, empty for
+// the root), a tab, the guess, a tab, and the remaining possible count.
+fn save_decision_tree(tree: &DecisionTree, file_path: &str) -> std::io::Result<()> {
+    let mut lines: Vec<String> = tree
+        .iter()
+        .map(|(response_path, &(guess, possibles))| {
+            let path_str = response_path
+                .iter()
+                .map(|keys| keys.to_string())
+                .collect::<Vec<_>>()
+                .join(";");
+            format!("{}\t{}\t{}", path_str, guess, possibles)
+        })
+        .collect();
+    lines.sort();
+    std::fs::write(file_path, lines.join("\n") + "\n")
+}
+
+fn load_decision_tree(file_path: &str) -> std::io::Result<DecisionTree> {
+    let content = std::fs::read_to_string(file_path)?;
+    let tree = content
+        .lines()
+        .map(|line| {
+            let mut fields = line.split('\t');
+            let path_str = fields.next().unwrap();
+            let guess = fields.next().unwrap().parse().unwrap();
+            let possibles = fields.next().unwrap().parse().unwrap();
+            let response_path = if path_str.is_empty() {
+                Vec::new()
+            } else {
+                path_str
+                    .split(';')
+                    .map(|pair| {
+                        let mut parts = pair.split(',');
+                        let exact_count = parts.next().unwrap().parse().unwrap();
+                        let color_count = parts.next().unwrap().parse().unwrap();
+                        MatchKeys::new(exact_count, color_count)
+                    })
+                    .collect()
+            };
+            (response_path, (guess, possibles))
+        })
+        .collect();
+    Ok(tree)
+}
+
+// Like play_interactive, but every guess is an O(1) lookup into a
+// precomputed DecisionTree instead of a fresh Game::get_guess call.
+fn play_interactive_from_tree(hole_count: u32, color_chars: &[char], tree: &DecisionTree) {
+    let board = Board::new(color_chars.len() as u32, hole_count);
+    let mut path: Vec<MatchKeys> = Vec::new();
+    loop {
+        let &(guess, possibles) = tree
+            .get(&path)
+            .expect("decision tree has no entry for this response path");
+        if possibles == 1 {
+            println!("Answer: {}", board.pattern_to_string(guess, color_chars));
+            break;
+        }
+        println!(
+            "Guess: {} ({} possibles)",
+            board.pattern_to_string(guess, color_chars),
+            possibles
+        );
+        let keys = read_match_keys();
+        if keys == MatchKeys::new(hole_count, 0) {
+            println!("Lucky guess!");
+            break;
+        }
+        path.push(keys);
     }
 }
 
@@ -185,10 +489,18 @@ fn read_match_keys() -> MatchKeys {
     MatchKeys::new(exact_count, color_count)
 }
 
-fn play_interactive(hole_count: u32, color_chars: &[char]) {
-    let mut game = Game::new(color_chars.len() as u32, hole_count);
+fn new_game(color_count: u32, hole_count: u32, lazy: bool) -> Game {
+    if lazy {
+        Game::new_lazy(color_count, hole_count)
+    } else {
+        Game::new(color_count, hole_count)
+    }
+}
+
+fn play_interactive(hole_count: u32, color_chars: &[char], strategy: Strategy, lazy: bool) {
+    let mut game = new_game(color_chars.len() as u32, hole_count, lazy);
     loop {
-        let (guess, possibles) = game.get_guess();
+        let (guess, possibles) = game.get_guess(strategy);
         if possibles == 1 {
             println!(
                 "Answer: {}",
@@ -211,11 +523,11 @@ fn play_interactive(hole_count: u32, color_chars: &[char]) {
     }
 }
 
-fn play_auto(hole_count: u32, color_chars: &[char], code: &str) {
-    let mut game = Game::new(color_chars.len() as u32, hole_count);
+fn play_auto(hole_count: u32, color_chars: &[char], code: &str, strategy: Strategy, lazy: bool) {
+    let mut game = new_game(color_chars.len() as u32, hole_count, lazy);
     let code = game.board.string_to_pattern(code, color_chars);
     loop {
-        let (guess, possibles) = game.get_guess();
+        let (guess, possibles) = game.get_guess(strategy);
         if possibles == 1 {
             println!(
                 "Answer: {}",
@@ -239,12 +551,18 @@ fn play_auto(hole_count: u32, color_chars: &[char], code: &str) {
     }
 }
 
-fn count_guesses(hole_count: u32, color_chars: &[char], code: u32) -> u32 {
-    let mut game = Game::new(color_chars.len() as u32, hole_count);
+fn count_guesses(
+    hole_count: u32,
+    color_chars: &[char],
+    code: u32,
+    strategy: Strategy,
+    lazy: bool,
+) -> u32 {
+    let mut game = new_game(color_chars.len() as u32, hole_count, lazy);
     let mut count = 0;
     loop {
         count += 1;
-        let (guess, possibles) = game.get_guess();
+        let (guess, possibles) = game.get_guess(strategy);
         if possibles == 1 {
             break;
         }
@@ -257,13 +575,13 @@ fn count_guesses(hole_count: u32, color_chars: &[char], code: u32) -> u32 {
     count
 }
 
-fn play_all_patterns(hole_count: u32, color_chars: &Vec<char>) {
+fn play_all_patterns(hole_count: u32, color_chars: &Vec<char>, strategy: Strategy, lazy: bool) {
     let mut total_guesses = 0;
     let mut max_guesses = 0;
     let color_count = color_chars.len() as u32;
     let total_patterns = color_count.pow(hole_count);
     for code in 0..total_patterns {
-        let guesses = count_guesses(hole_count, color_chars, code);
+        let guesses = count_guesses(hole_count, color_chars, code, strategy, lazy);
         max_guesses = max_guesses.max(guesses);
         total_guesses += guesses;
         println!(
@@ -277,6 +595,21 @@ fn play_all_patterns(hole_count: u32, color_chars: &Vec<char>) {
         "Average guesses: {}",
         total_guesses as f64 / total_patterns as f64
     );
+
+    println!("\nStrategy comparison:");
+    for candidate in Strategy::ALL {
+        let (candidate_max, candidate_total) =
+            (0..total_patterns).fold((0, 0), |(max, total), code| {
+                let guesses = count_guesses(hole_count, color_chars, code, candidate, lazy);
+                (max.max(guesses), total + guesses)
+            });
+        println!(
+            "{:<14} max {:>3}  avg {:.4}",
+            candidate.name(),
+            candidate_max,
+            candidate_total as f64 / total_patterns as f64
+        );
+    }
 }
 
 /*
@@ -314,21 +647,66 @@ fn show_example_matches() {
 }
 */
 
+fn extract_strategy_flag(args: &mut Vec<String>) -> Strategy {
+    let index = match args.iter().position(|arg| arg.starts_with("--strategy")) {
+        Some(index) => index,
+        None => return Strategy::Minimax,
+    };
+    let flag = args.remove(index);
+    let value = match flag.strip_prefix("--strategy=") {
+        Some(value) => value.to_string(),
+        None => args.remove(index),
+    };
+    value.parse().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    })
+}
+
+fn extract_lazy_flag(args: &mut Vec<String>) -> bool {
+    match args.iter().position(|arg| arg == "--lazy") {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
 fn main() {
-    let args: Vec<_> = std::env::args().skip(1).collect();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let strategy = extract_strategy_flag(&mut args);
+    let lazy = extract_lazy_flag(&mut args);
     if args.len() < 2 {
-        eprintln!("Usage: mastermind <hole count> <peg chars>");
+        eprintln!("Usage: mastermind <hole count> <peg chars> [--strategy=<name>] [--lazy]");
         std::process::exit(1);
     }
     let hole_count = u32::from_str_radix(&args[0], 10).expect("Invalid hole count");
     let color_chars = args[1].chars().collect::<Vec<char>>();
     if args.len() == 2 {
-        play_interactive(hole_count, &color_chars);
+        play_interactive(hole_count, &color_chars, strategy, lazy);
     } else if args.len() == 3 && args[2] == "all" {
-        play_all_patterns(hole_count, &color_chars);
+        play_all_patterns(hole_count, &color_chars, strategy, lazy);
     } else if args.len() == 4 && args[2] == "guess" {
         let code = &args[3];
-        play_auto(hole_count, &color_chars, code);
+        play_auto(hole_count, &color_chars, code, strategy, lazy);
+    } else if args.len() == 4 && args[2] == "build-tree" {
+        let tree = build_full_decision_tree(color_chars.len() as u32, hole_count, strategy);
+        println!(
+            "Decision tree has {} nodes, worst case {} guesses",
+            tree.len(),
+            decision_tree_max_depth(&tree)
+        );
+        if let Err(err) = save_decision_tree(&tree, &args[3]) {
+            eprintln!("Failed to write decision tree: {}", err);
+            std::process::exit(1);
+        }
+    } else if args.len() == 4 && args[2] == "play-tree" {
+        let tree = load_decision_tree(&args[3]).unwrap_or_else(|err| {
+            eprintln!("Failed to read decision tree: {}", err);
+            std::process::exit(1);
+        });
+        play_interactive_from_tree(hole_count, &color_chars, &tree);
     } else {
         eprintln!("Illegal usage");
         std::process::exit(1);
@@ -346,6 +724,121 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_get_guess_entropy_strategy() {
+        let game = Game::new(3, 2);
+        let (guess, possibles) = game.get_guess(Strategy::Entropy);
+        assert_eq!(possibles, 9);
+        assert!(guess < game.board.total_pattern_count());
+    }
+
+    #[test]
+    fn test_get_guess_expected_size_strategy() {
+        let game = Game::new(3, 2);
+        let (guess, possibles) = game.get_guess(Strategy::ExpectedSize);
+        assert_eq!(possibles, 9);
+        assert!(guess < game.board.total_pattern_count());
+    }
+
+    #[test]
+    fn test_get_guess_most_parts_strategy() {
+        let game = Game::new(3, 2);
+        let (guess, possibles) = game.get_guess(Strategy::MostParts);
+        assert_eq!(possibles, 9);
+        assert!(guess < game.board.total_pattern_count());
+    }
+
+    #[test]
+    fn test_strategy_from_str() {
+        assert!("minimax".parse::<Strategy>().is_ok());
+        assert!("expected-size".parse::<Strategy>().is_ok());
+        assert!("most-parts".parse::<Strategy>().is_ok());
+        assert!("entropy".parse::<Strategy>().is_ok());
+        assert!("bogus".parse::<Strategy>().is_err());
+    }
+
+    #[test]
+    fn test_pack_unpack_match_roundtrip() {
+        let board = Board::new(6, 4);
+        for exact_count in 0..=4 {
+            for color_count in 0..=(4 - exact_count) {
+                let match_keys = MatchKeys::new(exact_count, color_count);
+                assert_eq!(board.unpack_match(board.pack_match(match_keys)), match_keys);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_game_matches_full_game() {
+        let mut full = Game::new(3, 2);
+        let mut lazy = Game::new_lazy(3, 2);
+        loop {
+            let (full_guess, full_possibles) = full.get_guess(Strategy::Minimax);
+            let (lazy_guess, lazy_possibles) = lazy.get_guess(Strategy::Minimax);
+            assert_eq!(full_guess, lazy_guess);
+            assert_eq!(full_possibles, lazy_possibles);
+            if full_possibles == 1 {
+                break;
+            }
+            let keys = full.board.compute_match(full_guess, 0);
+            full.apply_match(full_guess, keys);
+            lazy.apply_match(lazy_guess, keys);
+        }
+    }
+
+    #[test]
+    fn test_lazy_cache_stays_bounded_during_play() {
+        let game = Game::new_lazy(4, 4);
+        game.get_guess(Strategy::Minimax);
+        match &game.matches {
+            MatchCache::Lazy(cache) => {
+                // get_guess must not memoize a row per guess it merely scores,
+                // or the cache would fill to total_pattern_count() on move one.
+                assert!(cache.lock().unwrap().len() < game.board.total_pattern_count() as usize);
+            }
+            MatchCache::Full(_) => panic!("expected a lazy cache"),
+        }
+    }
+
+    #[test]
+    fn test_decision_tree_matches_live_play() {
+        let board = Board::new(3, 2);
+        let tree = build_full_decision_tree(3, 2, Strategy::Minimax);
+
+        for code in 0..board.total_pattern_count() {
+            let mut game = Game::new(3, 2);
+            let mut path = Vec::new();
+            loop {
+                let (live_guess, live_possibles) = game.get_guess(Strategy::Minimax);
+                let &(tree_guess, tree_possibles) = tree.get(&path).unwrap();
+                assert_eq!(live_guess, tree_guess);
+                assert_eq!(live_possibles, tree_possibles);
+                if live_possibles == 1 {
+                    break;
+                }
+                let keys = board.compute_match(live_guess, code);
+                game.apply_match(live_guess, keys);
+                if keys == MatchKeys::new(2, 0) {
+                    break;
+                }
+                path.push(keys);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decision_tree_save_load_roundtrip() {
+        let tree = build_full_decision_tree(3, 2, Strategy::Minimax);
+        let file_path = std::env::temp_dir().join("mastermind_test_decision_tree.txt");
+        let file_path = file_path.to_str().unwrap();
+
+        save_decision_tree(&tree, file_path).unwrap();
+        let loaded = load_decision_tree(file_path).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+
+        assert_eq!(tree, loaded);
+    }
+
     #[test]
     fn test_total_pattern_count() {
         let board = Board::new(3, 2);